@@ -0,0 +1,907 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u32,
+    pub journal_date: NaiveDate,
+    pub account_id: u32,
+    pub amount_debt: f64,
+    pub amount_credit: f64,
+    pub total: f64,
+    pub reconciled: bool,
+    pub isdeleted: String,
+    pub transaction_id: Option<u32>,
+}
+
+/// A transaction groups one or more journal lines that must post atomically
+/// and balance: the sum of debits must equal the sum of credits. `id` is a
+/// placeholder (like `JournalEntry::id` before `add_entry`): `post_transaction`
+/// ignores it, assigns the transaction id itself and returns it.
+pub struct Transaction {
+    pub id: u32,
+    pub date: NaiveDate,
+    pub description: String,
+    pub lines: Vec<JournalEntry>,
+}
+
+/// One row of the `transactions` sidecar: `post_transaction`'s description
+/// for a `transaction_id`, kept separately from `JournalEntry` so it isn't
+/// repeated on every line of the transaction.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TransactionRecord {
+    id: u32,
+    description: String,
+}
+
+const BALANCE_EPSILON: f64 = 1e-6;
+
+/// Fallible operations in this module report through this enum (`load`
+/// returns a plain `io::Result`) instead of panicking, so a corrupt file or
+/// a read-only directory is a handled error rather than a crash.
+#[derive(Debug)]
+pub enum AcctError {
+    Io(io::Error),
+    Parse(String),
+    NotFound(u32),
+    Imbalance { total_debt: f64, total_credit: f64 },
+    EmptyTransaction,
+}
+
+impl fmt::Display for AcctError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AcctError::Io(e) => write!(f, "io error: {}", e),
+            AcctError::Parse(msg) => write!(f, "parse error: {}", msg),
+            AcctError::NotFound(id) => write!(f, "no entry with id {}", id),
+            AcctError::Imbalance { total_debt, total_credit } => write!(
+                f,
+                "transaction does not balance: debits {} != credits {}",
+                total_debt, total_credit
+            ),
+            AcctError::EmptyTransaction => write!(f, "transaction has no lines"),
+        }
+    }
+}
+
+impl std::error::Error for AcctError {}
+
+impl From<io::Error> for AcctError {
+    fn from(e: io::Error) -> Self {
+        AcctError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for AcctError {
+    fn from(e: serde_json::Error) -> Self {
+        AcctError::Parse(e.to_string())
+    }
+}
+
+/// Aggregate debit/credit rollup for one account, as produced by `trial_balance`.
+#[derive(Debug)]
+pub struct AccountBalance {
+    pub account_id: u32,
+    pub total_debt: f64,
+    pub total_credit: f64,
+    pub total: f64,
+}
+
+/// One ledger line plus the running balance of its account up to and
+/// including that line, as produced by `account_ledger`.
+#[derive(Debug)]
+pub struct LedgerRow {
+    pub entry: JournalEntry,
+    pub running_balance: f64,
+}
+
+/// How often a `RecurringEntry` reposts its template.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Cadence {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A standing journal entry (rent, payroll, ...) that `run_due` reposts on
+/// its `cadence` until `end` (if any) is passed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecurringEntry {
+    pub id: u32,
+    pub template: JournalEntry,
+    pub cadence: Cadence,
+    pub next_run: NaiveDate,
+    pub end: Option<NaiveDate>,
+}
+
+/// Advances `date` by one `cadence` period. `anchor_day` is the
+/// day-of-month the recurrence was originally scheduled for (the template's
+/// day), so a 31st template lands on the last day of shorter months without
+/// drifting: Jan 31 -> Feb 28 -> Mar 31, not Jan 31 -> Feb 28 -> Mar 28.
+fn advance_cadence(date: NaiveDate, cadence: Cadence, anchor_day: u32) -> NaiveDate {
+    match cadence {
+        Cadence::Daily => date + chrono::Duration::days(1),
+        Cadence::Weekly => date + chrono::Duration::days(7),
+        Cadence::Monthly => add_months(date, 1, anchor_day),
+        Cadence::Yearly => add_months(date, 12, anchor_day),
+    }
+}
+
+fn add_months(date: NaiveDate, months: i32, anchor_day: u32) -> NaiveDate {
+    use chrono::Datelike;
+    let total_months = date.year() * 12 + date.month0() as i32 + months;
+    let year = total_months.div_euclid(12);
+    let month = total_months.rem_euclid(12) as u32 + 1;
+    let day = anchor_day.min(last_day_of_month(year, month));
+    NaiveDate::from_ymd_opt(year, month, day).expect("valid calendar date")
+}
+
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    use chrono::Datelike;
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .expect("valid calendar date")
+        .pred_opt()
+        .expect("valid calendar date")
+        .day()
+}
+
+/// The result of `import_csv`: how many rows were imported, plus a
+/// line-numbered warning for every row that was skipped instead of
+/// panicking the whole import.
+pub struct CsvImportReport {
+    pub imported: usize,
+    pub warnings: Vec<String>,
+}
+
+/// Append-only storage: `log_filename` only ever has records appended to it
+/// (a new version per update/delete, never rewritten in place) until
+/// `compact` rewrites it whole, and
+/// `index` is the in-memory mirror of a sidecar file mapping each id to the
+/// byte offset of its *latest* version in the log, kept sorted by id so a
+/// lookup is a binary search instead of a scan over the whole log.
+/// `recurring` is a much smaller side store of standing entries, so it's
+/// kept simple: loaded fully into memory and rewritten whole on each change.
+/// `transactions` holds each posted `Transaction`'s description, keyed by
+/// `transaction_id`; it is append-only (a posted description never
+/// changes) so it's just appended to and, unlike the log, never compacted.
+pub struct AccountingSystem {
+    log_filename: String,
+    index_filename: String,
+    index: Vec<(u32, u64)>,
+    next_id: u32,
+    next_transaction_id: u32,
+    recurring: Vec<RecurringEntry>,
+    recurring_next_id: u32,
+    recurring_filename: String,
+    transactions: BTreeMap<u32, String>,
+    transactions_filename: String,
+    /// Line-numbered warnings about malformed records encountered while
+    /// reading or rebuilding the index, or loading recurring or transaction
+    /// definitions.
+    pub warnings: Vec<String>,
+}
+
+impl AccountingSystem {
+    pub fn new(filename: &str) -> Result<Self, AcctError> {
+        let index_filename = format!("{}.idx", filename);
+        let recurring_filename = format!("{}.recurring", filename);
+        let transactions_filename = format!("{}.transactions", filename);
+        let mut sys = Self {
+            log_filename: filename.to_string(),
+            index_filename,
+            index: Vec::new(),
+            next_id: 1,
+            next_transaction_id: 1,
+            recurring: Vec::new(),
+            recurring_next_id: 1,
+            recurring_filename,
+            transactions: BTreeMap::new(),
+            transactions_filename,
+            warnings: Vec::new(),
+        };
+        sys.load()?;
+        sys.load_recurring()?;
+        sys.load_transactions()?;
+        Ok(sys)
+    }
+
+    /// Loads the sidecar index (rebuilding it from the log if missing), then
+    /// recovers `next_id` from the index alone and `next_transaction_id`
+    /// from a single sequential pass over the log — not one `read_record_at`
+    /// per index entry, which would reopen and seek the log once per record
+    /// just to start up.
+    pub fn load(&mut self) -> io::Result<()> {
+        if !Path::new(&self.log_filename).exists() {
+            return Ok(());
+        }
+        if Path::new(&self.index_filename).exists() {
+            self.load_index()?;
+        } else {
+            self.rebuild_index()?;
+        }
+
+        for &(id, _) in &self.index {
+            if id >= self.next_id {
+                self.next_id = id + 1;
+            }
+        }
+
+        let file = File::open(&self.log_filename)?;
+        let reader = BufReader::new(file);
+        for line in reader.lines() {
+            let line = line?;
+            if let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) {
+                if let Some(tx_id) = entry.transaction_id {
+                    if tx_id >= self.next_transaction_id {
+                        self.next_transaction_id = tx_id + 1;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load_index(&mut self) -> io::Result<()> {
+        let file = File::open(&self.index_filename)?;
+        let reader = BufReader::new(file);
+        self.index.clear();
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut parts = line.split(' ');
+            let id = parts.next().and_then(|s| s.parse::<u32>().ok());
+            let offset = parts.next().and_then(|s| s.parse::<u64>().ok());
+            match (id, offset) {
+                (Some(id), Some(offset)) => self.index.push((id, offset)),
+                _ => {
+                    if !line.trim().is_empty() {
+                        self.warnings.push(format!("index line {}: malformed entry {:?}, skipping", line_no + 1, line));
+                    }
+                }
+            }
+        }
+        self.index.sort_by_key(|&(id, _)| id);
+        Ok(())
+    }
+
+    /// Scans the log sequentially, keeping the offset of the last occurrence
+    /// of each id (later occurrences are newer versions). A final line with
+    /// no trailing newline (a truncated write) fails to parse and is
+    /// recorded as a warning rather than treated as an error.
+    fn rebuild_index(&mut self) -> io::Result<()> {
+        let file = File::open(&self.log_filename)?;
+        let reader = BufReader::new(file);
+        let mut latest: Vec<(u32, u64)> = Vec::new();
+        let mut offset: u64 = 0;
+
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_len = line.len() as u64 + 1; // + '\n'
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(entry) => match latest.iter_mut().find(|(id, _)| *id == entry.id) {
+                    Some(slot) => slot.1 = offset,
+                    None => latest.push((entry.id, offset)),
+                },
+                Err(e) => {
+                    if !line.trim().is_empty() {
+                        self.warnings.push(format!("log line {}: {}, skipping", line_no + 1, e));
+                    }
+                }
+            }
+            offset += line_len;
+        }
+
+        latest.sort_by_key(|&(id, _)| id);
+        self.index = latest;
+        self.write_index()?;
+        Ok(())
+    }
+
+    fn write_index(&self) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&self.index_filename)?;
+        for &(id, offset) in &self.index {
+            writeln!(file, "{} {}", id, offset)?;
+        }
+        Ok(())
+    }
+
+    /// Appends `entry` as a new record in the log and returns the byte
+    /// offset it was written at.
+    fn append_record(&self, entry: &JournalEntry) -> Result<u64, AcctError> {
+        let mut file = OpenOptions::new().append(true).create(true).open(&self.log_filename)?;
+        let offset = file.seek(SeekFrom::End(0))?;
+        let json = serde_json::to_string(entry)?;
+        writeln!(file, "{}", json)?;
+        Ok(offset)
+    }
+
+    fn read_record_at(&self, offset: u64) -> Result<JournalEntry, AcctError> {
+        let mut file = File::open(&self.log_filename)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        Ok(serde_json::from_str(line.trim_end())?)
+    }
+
+    /// Binary-searches the index for `id`'s latest version; id gaps (no
+    /// entry was ever assigned that id) simply miss the search and return
+    /// `None`, same as a soft-deleted id.
+    pub fn get_entry(&self, id: u32) -> Result<Option<JournalEntry>, AcctError> {
+        let pos = match self.index.binary_search_by_key(&id, |&(i, _)| i) {
+            Ok(pos) => pos,
+            Err(_) => return Ok(None),
+        };
+        let entry = self.read_record_at(self.index[pos].1)?;
+        if entry.isdeleted == "no" {
+            Ok(Some(entry))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Scan-everything path for reports: a single sequential read of the
+    /// log, keeping the latest version of each id, rather than
+    /// `read_record_at`'s one-`File::open`-and-seek-per-record random access
+    /// (the right tradeoff for `get_entry`'s single lookup, wasteful for a
+    /// full scan).
+    pub fn list_entries(&self) -> Result<Vec<JournalEntry>, AcctError> {
+        if !Path::new(&self.log_filename).exists() {
+            return Ok(Vec::new());
+        }
+        let file = File::open(&self.log_filename)?;
+        let reader = BufReader::new(file);
+        let mut latest: BTreeMap<u32, JournalEntry> = BTreeMap::new();
+        for line in reader.lines() {
+            let line = line?;
+            if let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) {
+                latest.insert(entry.id, entry);
+            }
+        }
+        Ok(latest.into_values().filter(|e| e.isdeleted == "no").collect())
+    }
+
+    pub fn add_entry(&mut self, mut entry: JournalEntry) -> Result<u32, AcctError> {
+        entry.id = self.next_id;
+        self.next_id += 1;
+        // Calculate total
+        entry.total = entry.amount_debt - entry.amount_credit;
+        let offset = self.append_record(&entry)?;
+        self.index.push((entry.id, offset));
+        self.write_index()?;
+        Ok(entry.id)
+    }
+
+    pub fn update_entry(&mut self, id: u32, mut updated: JournalEntry) -> Result<(), AcctError> {
+        if self.get_entry(id)?.is_none() {
+            return Err(AcctError::NotFound(id));
+        }
+        // Recalculate total
+        updated.total = updated.amount_debt - updated.amount_credit;
+        updated.id = id;
+        let offset = self.append_record(&updated)?;
+        if let Ok(pos) = self.index.binary_search_by_key(&id, |&(i, _)| i) {
+            self.index[pos].1 = offset;
+        }
+        self.write_index()?;
+        Ok(())
+    }
+
+    pub fn delete_entry(&mut self, id: u32) -> Result<(), AcctError> {
+        let mut tombstone = self.get_entry(id)?.ok_or(AcctError::NotFound(id))?;
+        tombstone.isdeleted = "yes".to_string();
+        let offset = self.append_record(&tombstone)?;
+        if let Ok(pos) = self.index.binary_search_by_key(&id, |&(i, _)| i) {
+            self.index[pos].1 = offset;
+        }
+        self.write_index()?;
+        Ok(())
+    }
+
+    /// Rewrites the log keeping only the latest, non-deleted version of each
+    /// id, and regenerates the index to match. Run periodically to reclaim
+    /// space from superseded versions and tombstones.
+    pub fn compact(&mut self) -> Result<(), AcctError> {
+        let live = self.list_entries()?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&self.log_filename)?;
+        let mut offset: u64 = 0;
+        let mut new_index = Vec::with_capacity(live.len());
+        for entry in &live {
+            let json = serde_json::to_string(entry)?;
+            writeln!(file, "{}", json)?;
+            new_index.push((entry.id, offset));
+            offset += json.len() as u64 + 1;
+        }
+        self.index = new_index;
+        self.write_index()?;
+        Ok(())
+    }
+
+    /// Posts a balanced `Transaction` as a group of journal lines sharing one
+    /// `transaction_id`. All lines are assigned ids, serialized, and then
+    /// appended to the log in a single write, so a rejected transaction
+    /// writes nothing and a serialization failure can't leave a partial write.
+    pub fn post_transaction(&mut self, tx: Transaction) -> Result<u32, AcctError> {
+        if tx.lines.is_empty() {
+            return Err(AcctError::EmptyTransaction);
+        }
+        let total_debt: f64 = tx.lines.iter().map(|l| l.amount_debt).sum();
+        let total_credit: f64 = tx.lines.iter().map(|l| l.amount_credit).sum();
+        if (total_debt - total_credit).abs() > BALANCE_EPSILON {
+            return Err(AcctError::Imbalance { total_debt, total_credit });
+        }
+
+        let tx_id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+
+        let mut file = OpenOptions::new().append(true).create(true).open(&self.log_filename)?;
+        let mut offset = file.seek(SeekFrom::End(0))?;
+        let mut batch = String::new();
+        let mut new_index = Vec::with_capacity(tx.lines.len());
+        for mut line in tx.lines {
+            line.id = self.next_id;
+            self.next_id += 1;
+            line.journal_date = tx.date;
+            line.total = line.amount_debt - line.amount_credit;
+            line.isdeleted = "no".to_string();
+            line.transaction_id = Some(tx_id);
+            let json = serde_json::to_string(&line)?;
+            new_index.push((line.id, offset));
+            offset += json.len() as u64 + 1;
+            batch.push_str(&json);
+            batch.push('\n');
+        }
+        file.write_all(batch.as_bytes())?;
+        self.index.extend(new_index);
+        self.write_index()?;
+        self.append_transaction_record(tx_id, &tx.description)?;
+        self.transactions.insert(tx_id, tx.description);
+        Ok(tx_id)
+    }
+
+    /// Returns the `description` passed to `post_transaction` for `tx_id`,
+    /// or `None` if no transaction with that id has been posted.
+    pub fn transaction_description(&self, tx_id: u32) -> Option<&str> {
+        self.transactions.get(&tx_id).map(String::as_str)
+    }
+
+    pub fn list_transaction(&self, tx_id: u32) -> Result<Vec<JournalEntry>, AcctError> {
+        Ok(self
+            .list_entries()?
+            .into_iter()
+            .filter(|e| e.transaction_id == Some(tx_id))
+            .collect())
+    }
+
+    /// Groups non-deleted entries by `account_id` and sums their debits and
+    /// credits, optionally restricted to entries on or before `as_of`. For a
+    /// balanced book the grand totals (sum of every `AccountBalance.total`)
+    /// net to zero.
+    pub fn trial_balance(&self, as_of: Option<NaiveDate>) -> Result<Vec<AccountBalance>, AcctError> {
+        let mut balances: Vec<AccountBalance> = Vec::new();
+        for entry in self.list_entries()? {
+            if let Some(cutoff) = as_of {
+                if entry.journal_date > cutoff {
+                    continue;
+                }
+            }
+            match balances.iter_mut().find(|b| b.account_id == entry.account_id) {
+                Some(b) => {
+                    b.total_debt += entry.amount_debt;
+                    b.total_credit += entry.amount_credit;
+                    b.total += entry.total;
+                }
+                None => balances.push(AccountBalance {
+                    account_id: entry.account_id,
+                    total_debt: entry.amount_debt,
+                    total_credit: entry.amount_credit,
+                    total: entry.total,
+                }),
+            }
+        }
+        balances.sort_by_key(|b| b.account_id);
+        Ok(balances)
+    }
+
+    /// Returns entries for `account_id` dated within `[from, to]`, sorted by
+    /// `journal_date`, each carrying the account's running balance so far.
+    pub fn account_ledger(&self, account_id: u32, from: NaiveDate, to: NaiveDate) -> Result<Vec<LedgerRow>, AcctError> {
+        let mut matching: Vec<JournalEntry> = self
+            .list_entries()?
+            .into_iter()
+            .filter(|e| e.account_id == account_id && e.journal_date >= from && e.journal_date <= to)
+            .collect();
+        matching.sort_by_key(|e| e.journal_date);
+
+        let mut running_balance = 0.0;
+        Ok(matching
+            .into_iter()
+            .map(|entry| {
+                running_balance += entry.total;
+                LedgerRow { entry, running_balance }
+            })
+            .collect())
+    }
+
+    /// Writes every non-deleted entry to `path` as a header row plus one CSV
+    /// row per entry, so books can be handed off to a spreadsheet.
+    pub fn export_csv(&self, path: &str) -> Result<(), AcctError> {
+        let mut file = File::create(path)?;
+        writeln!(file, "id,journal_date,account_id,amount_debt,amount_credit,total,reconciled")?;
+        for entry in self.list_entries()? {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{}",
+                entry.id,
+                entry.journal_date,
+                entry.account_id,
+                entry.amount_debt,
+                entry.amount_credit,
+                entry.total,
+                entry.reconciled
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Streams rows from a CSV file produced by `export_csv` (or a bank
+    /// export of the same shape) and appends them as new entries. Each row
+    /// gets a fresh id via `next_id` and a recomputed `total`; malformed rows
+    /// are collected into the report's `warnings` with their line number and
+    /// skipped rather than panicking the whole import.
+    pub fn import_csv(&mut self, path: &str) -> Result<CsvImportReport, AcctError> {
+        let file = File::open(path)?;
+        let reader = BufReader::new(file);
+        let mut imported = 0;
+        let mut warnings = Vec::new();
+
+        for (line_no, line) in reader.lines().enumerate().skip(1) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() < 7 {
+                warnings.push(format!("line {}: expected 7 columns, got {}, skipping", line_no + 1, fields.len()));
+                continue;
+            }
+
+            let journal_date = match NaiveDate::parse_from_str(fields[1], "%Y-%m-%d") {
+                Ok(d) => d,
+                Err(e) => {
+                    warnings.push(format!("line {}: invalid journal_date {:?}: {}, skipping", line_no + 1, fields[1], e));
+                    continue;
+                }
+            };
+            let account_id: u32 = match fields[2].parse() {
+                Ok(v) => v,
+                Err(e) => {
+                    warnings.push(format!("line {}: invalid account_id {:?}: {}, skipping", line_no + 1, fields[2], e));
+                    continue;
+                }
+            };
+            let amount_debt: f64 = match fields[3].parse() {
+                Ok(v) => v,
+                Err(e) => {
+                    warnings.push(format!("line {}: invalid amount_debt {:?}: {}, skipping", line_no + 1, fields[3], e));
+                    continue;
+                }
+            };
+            let amount_credit: f64 = match fields[4].parse() {
+                Ok(v) => v,
+                Err(e) => {
+                    warnings.push(format!("line {}: invalid amount_credit {:?}: {}, skipping", line_no + 1, fields[4], e));
+                    continue;
+                }
+            };
+            let reconciled: bool = match fields[6].trim().parse() {
+                Ok(v) => v,
+                Err(e) => {
+                    warnings.push(format!("line {}: invalid reconciled {:?}: {}, skipping", line_no + 1, fields[6], e));
+                    continue;
+                }
+            };
+
+            let entry = JournalEntry {
+                id: self.next_id,
+                journal_date,
+                account_id,
+                amount_debt,
+                amount_credit,
+                total: amount_debt - amount_credit,
+                reconciled,
+                isdeleted: "no".to_string(),
+                transaction_id: None,
+            };
+            self.next_id += 1;
+            let offset = self.append_record(&entry)?;
+            self.index.push((entry.id, offset));
+            imported += 1;
+        }
+
+        self.index.sort_by_key(|&(id, _)| id);
+        self.write_index()?;
+        Ok(CsvImportReport { imported, warnings })
+    }
+
+    fn load_recurring(&mut self) -> io::Result<()> {
+        self.recurring.clear();
+        if !Path::new(&self.recurring_filename).exists() {
+            return Ok(());
+        }
+        let file = File::open(&self.recurring_filename)?;
+        let reader = BufReader::new(file);
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            match serde_json::from_str::<RecurringEntry>(&line) {
+                Ok(rec) => {
+                    if rec.id >= self.recurring_next_id {
+                        self.recurring_next_id = rec.id + 1;
+                    }
+                    self.recurring.push(rec);
+                }
+                Err(e) => {
+                    if !line.trim().is_empty() {
+                        self.warnings.push(format!("recurring line {}: {}, skipping", line_no + 1, e));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn load_transactions(&mut self) -> io::Result<()> {
+        self.transactions.clear();
+        if !Path::new(&self.transactions_filename).exists() {
+            return Ok(());
+        }
+        let file = File::open(&self.transactions_filename)?;
+        let reader = BufReader::new(file);
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            match serde_json::from_str::<TransactionRecord>(&line) {
+                Ok(rec) => {
+                    self.transactions.insert(rec.id, rec.description);
+                }
+                Err(e) => {
+                    if !line.trim().is_empty() {
+                        self.warnings.push(format!("transactions line {}: {}, skipping", line_no + 1, e));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn append_transaction_record(&self, id: u32, description: &str) -> Result<(), AcctError> {
+        let mut file = OpenOptions::new().append(true).create(true).open(&self.transactions_filename)?;
+        let json = serde_json::to_string(&TransactionRecord { id, description: description.to_string() })?;
+        writeln!(file, "{}", json)?;
+        Ok(())
+    }
+
+    fn save_recurring(&self) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&self.recurring_filename)?;
+        for rec in &self.recurring {
+            let json = serde_json::to_string(rec).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            writeln!(file, "{}", json)?;
+        }
+        Ok(())
+    }
+
+    pub fn recur_add(&mut self, template: JournalEntry, cadence: Cadence, next_run: NaiveDate, end: Option<NaiveDate>) -> Result<u32, AcctError> {
+        let id = self.recurring_next_id;
+        self.recurring_next_id += 1;
+        self.recurring.push(RecurringEntry { id, template, cadence, next_run, end });
+        self.save_recurring()?;
+        Ok(id)
+    }
+
+    pub fn recur_list(&self) -> &[RecurringEntry] {
+        &self.recurring
+    }
+
+    /// Posts one fresh `JournalEntry` for every `next_run <= today` on each
+    /// recurring template, advancing `next_run` by its `cadence` each time so
+    /// a template that's gone unrun for a while catches up one period per
+    /// post, and stops advancing once `end` (if any) is passed. Returns the
+    /// number of entries posted.
+    pub fn run_due(&mut self, today: NaiveDate) -> Result<u32, AcctError> {
+        let mut posted = 0;
+        for i in 0..self.recurring.len() {
+            loop {
+                let rec = &self.recurring[i];
+                if rec.next_run > today {
+                    break;
+                }
+                if let Some(end) = rec.end {
+                    if rec.next_run > end {
+                        break;
+                    }
+                }
+                let run_date = rec.next_run;
+                let cadence = rec.cadence;
+                let anchor_day = {
+                    use chrono::Datelike;
+                    rec.template.journal_date.day()
+                };
+                let mut entry = rec.template.clone();
+                entry.journal_date = run_date;
+                entry.isdeleted = "no".to_string();
+                entry.transaction_id = None;
+                self.add_entry(entry)?;
+                posted += 1;
+                self.recurring[i].next_run = advance_cadence(run_date, cadence, anchor_day);
+            }
+        }
+        self.save_recurring()?;
+        Ok(posted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Each test gets its own log filename under the OS temp dir so
+    /// parallel test runs don't share a log, index, recurring, or
+    /// transactions sidecar file.
+    fn temp_log_path(tag: &str) -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir()
+            .join(format!("accounting_test_{}_{}_{}.jsonl", std::process::id(), tag, n))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn cleanup(log_path: &str) {
+        for suffix in ["", ".idx", ".recurring", ".transactions"] {
+            let _ = std::fs::remove_file(format!("{}{}", log_path, suffix));
+        }
+    }
+
+    fn line(account_id: u32, amount_debt: f64, amount_credit: f64) -> JournalEntry {
+        JournalEntry {
+            id: 0,
+            journal_date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            account_id,
+            amount_debt,
+            amount_credit,
+            total: amount_debt - amount_credit,
+            reconciled: false,
+            isdeleted: "no".to_string(),
+            transaction_id: None,
+        }
+    }
+
+    #[test]
+    fn post_transaction_rejects_imbalance() {
+        let path = temp_log_path("imbalance");
+        let mut sys = AccountingSystem::new(&path).unwrap();
+        let tx = Transaction {
+            id: 0,
+            date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            description: "rent".to_string(),
+            lines: vec![line(1, 100.0, 0.0), line(2, 0.0, 50.0)],
+        };
+        match sys.post_transaction(tx) {
+            Err(AcctError::Imbalance { total_debt, total_credit }) => {
+                assert_eq!(total_debt, 100.0);
+                assert_eq!(total_credit, 50.0);
+            }
+            other => panic!("expected Imbalance, got {:?}", other.map(|_| ())),
+        }
+        assert!(sys.list_entries().unwrap().is_empty());
+        cleanup(&path);
+    }
+
+    #[test]
+    fn post_transaction_accepts_within_epsilon() {
+        let path = temp_log_path("epsilon");
+        let mut sys = AccountingSystem::new(&path).unwrap();
+        let tx = Transaction {
+            id: 0,
+            date: NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+            description: "rounding".to_string(),
+            lines: vec![line(1, 100.0, 0.0), line(2, 0.0, 100.0 + BALANCE_EPSILON / 2.0)],
+        };
+        let tx_id = sys.post_transaction(tx).unwrap();
+        assert_eq!(sys.list_transaction(tx_id).unwrap().len(), 2);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn trial_balance_nets_to_zero() {
+        let path = temp_log_path("trial_balance");
+        let mut sys = AccountingSystem::new(&path).unwrap();
+        sys.add_entry(line(1, 200.0, 0.0)).unwrap();
+        sys.add_entry(line(2, 0.0, 150.0)).unwrap();
+        sys.add_entry(line(2, 0.0, 50.0)).unwrap();
+
+        let balances = sys.trial_balance(None).unwrap();
+        let grand_total: f64 = balances.iter().map(|b| b.total).sum();
+        assert!(grand_total.abs() < BALANCE_EPSILON, "grand total {} did not net to zero", grand_total);
+
+        let account2 = balances.iter().find(|b| b.account_id == 2).unwrap();
+        assert_eq!(account2.total_credit, 200.0);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn import_csv_skips_malformed_rows_with_line_numbers() {
+        let path = temp_log_path("import_csv");
+        let csv_path = format!("{}.csv", path);
+        std::fs::write(
+            &csv_path,
+            "id,journal_date,account_id,amount_debt,amount_credit,total,reconciled\n\
+             1,2026-01-01,1,100.0,0.0,100.0,false\n\
+             2,not-a-date,1,50.0,0.0,50.0,false\n\
+             3,2026-01-02,2,0.0,100.0,-100.0,false\n",
+        )
+        .unwrap();
+
+        let mut sys = AccountingSystem::new(&path).unwrap();
+        let report = sys.import_csv(&csv_path).unwrap();
+
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("line 3"), "warning was: {}", report.warnings[0]);
+        assert_eq!(sys.list_entries().unwrap().len(), 2);
+
+        cleanup(&path);
+        let _ = std::fs::remove_file(&csv_path);
+    }
+
+    #[test]
+    fn get_entry_after_delete_and_on_id_gap() {
+        let path = temp_log_path("get_entry");
+        let mut sys = AccountingSystem::new(&path).unwrap();
+        let id = sys.add_entry(line(1, 10.0, 0.0)).unwrap();
+
+        assert!(sys.get_entry(id).unwrap().is_some());
+        sys.delete_entry(id).unwrap();
+        assert!(sys.get_entry(id).unwrap().is_none(), "a soft-deleted entry should read back as absent");
+
+        // An id that was never assigned (a gap in the index) should miss the
+        // binary search rather than panicking or finding a neighbour.
+        assert!(sys.get_entry(id + 100).unwrap().is_none());
+        cleanup(&path);
+    }
+
+    #[test]
+    fn monthly_cadence_clamps_to_month_end_and_snaps_back() {
+        let anchor_day = 31;
+        let jan31 = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+
+        let feb28 = advance_cadence(jan31, Cadence::Monthly, anchor_day);
+        assert_eq!(feb28, NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+
+        let mar31 = advance_cadence(feb28, Cadence::Monthly, anchor_day);
+        assert_eq!(mar31, NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(), "should snap back to the 31st, not drift to the 28th");
+
+        let apr30 = advance_cadence(mar31, Cadence::Monthly, anchor_day);
+        assert_eq!(apr30, NaiveDate::from_ymd_opt(2026, 4, 30).unwrap());
+    }
+}
@@ -1,107 +1,6 @@
+use accounting::{AcctError, AccountingSystem, Cadence, JournalEntry, Transaction};
 use chrono::NaiveDate;
-use serde::{Deserialize, Serialize};
-use std::{fs::{OpenOptions, File}, io::{self, BufRead, BufReader, Write}, path::Path};
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct JournalEntry {
-    id: u32,
-    journal_date: NaiveDate,
-    account_id: u32,
-    amount_debt: f64,
-    amount_credit: f64,
-    total: f64,
-    reconciled: bool,
-    isdeleted: String,
-}
-
-struct AccountingSystem {
-    entries: Vec<JournalEntry>,
-    next_id: u32,
-    filename: String,
-}
-
-impl AccountingSystem {
-    fn new(filename: &str) -> Self {
-        let mut sys = Self {
-            entries: Vec::new(),
-            next_id: 1,
-            filename: filename.to_string(),
-        };
-        sys.load();
-        sys
-    }
-
-    fn load(&mut self) {
-        self.entries.clear();
-        if Path::new(&self.filename).exists() {
-            let file = File::open(&self.filename).expect("Cannot open entries file");
-            let reader = BufReader::new(file);
-            for line in reader.lines() {
-                if let Ok(l) = line {
-                    if let Ok(entry) = serde_json::from_str::<JournalEntry>(&l) {
-                        if entry.id >= self.next_id {
-                            self.next_id = entry.id + 1;
-                        }
-                        self.entries.push(entry);
-                    }
-                }
-            }
-        }
-    }
-
-    fn save(&self) {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&self.filename)
-            .expect("Cannot open for writing");
-        for entry in &self.entries {
-            let json = serde_json::to_string(entry).unwrap();
-            writeln!(file, "{}", json).unwrap();
-        }
-    }
-
-    fn add_entry(&mut self, mut entry: JournalEntry) {
-        entry.id = self.next_id;
-        self.next_id += 1;
-        // Calculate total
-        entry.total = entry.amount_debt - entry.amount_credit;
-        self.entries.push(entry);
-        self.save();
-    }
-
-    fn list_entries(&self) -> Vec<&JournalEntry> {
-        self.entries.iter().filter(|e| e.isdeleted == "no").collect()
-    }
-
-    fn update_entry(&mut self, id: u32, mut updated: JournalEntry) -> bool {
-        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id && e.isdeleted == "no") {
-            // Recalculate total
-            updated.total = updated.amount_debt - updated.amount_credit;
-            *entry = updated;
-            entry.id = id;
-            self.save();
-            true
-        } else {
-            false
-        }
-    }
-
-    fn delete_entry(&mut self, id: u32) -> bool {
-        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id && e.isdeleted == "no") {
-            entry.isdeleted = "yes".to_string();
-            self.save();
-            true
-        } else {
-            false
-        }
-    }
-
-    fn get_entry(&self, id: u32) -> Option<&JournalEntry> {
-        self.entries.iter().find(|e| e.id == id && e.isdeleted == "no")
-    }
-}
+use std::io::{self, Write};
 
 fn prompt(prompt: &str) -> String {
     print!("{}", prompt);
@@ -112,11 +11,20 @@ fn prompt(prompt: &str) -> String {
 }
 
 fn print_commands() {
-    println!("\nCommands: add, list, update, delete, get, exit");
+    println!("\nCommands: add, list, update, delete, get, post, trial, ledger, export, import, recur-add, recur-list, run, compact, exit");
 }
 
 fn main() {
-    let mut system = AccountingSystem::new("entries.txt");
+    let mut system = match AccountingSystem::new("entries.txt") {
+        Ok(system) => system,
+        Err(e) => {
+            eprintln!("Failed to open entries.txt: {}", e);
+            return;
+        }
+    };
+    for warning in &system.warnings {
+        eprintln!("Warning: {}", warning);
+    }
 
     println!("Welcome to Accounting Copilot CLI!");
     print_commands();
@@ -147,73 +55,291 @@ fn main() {
                     total: amount_debt - amount_credit, // for clarity, but add_entry also ensures this
                     reconciled,
                     isdeleted: "no".to_string(),
+                    transaction_id: None,
                 };
-                system.add_entry(entry);
-                println!("Entry added.");
+                match system.add_entry(entry) {
+                    Ok(id) => println!("Entry {} added.", id),
+                    Err(e) => println!("Add failed: {}", e),
+                }
                 print_commands();
             }
             "list" => {
                 println!("Current Entries:");
-                for entry in system.list_entries() {
-                    println!("{:?}", entry);
+                match system.list_entries() {
+                    Ok(entries) => {
+                        for entry in entries {
+                            println!("{:?}", entry);
+                        }
+                    }
+                    Err(e) => println!("List failed: {}", e),
                 }
                 print_commands();
             }
             "get" => {
                 let id: u32 = prompt("id: ").parse().unwrap_or(0);
-                if let Some(entry) = system.get_entry(id) {
-                    println!("{:?}", entry);
-                } else {
-                    println!("Entry not found.");
+                match system.get_entry(id) {
+                    Ok(Some(entry)) => println!("{:?}", entry),
+                    Ok(None) => println!("Entry not found."),
+                    Err(e) => println!("Get failed: {}", e),
                 }
                 print_commands();
             }
             "update" => {
                 let id: u32 = prompt("id: ").parse().unwrap_or(0);
-                if let Some(orig) = system.get_entry(id).cloned() {
-                    let date_str = prompt(&format!("journal_date (YYYY-MM-DD) [{}]: ", orig.journal_date));
-                    let journal_date = if date_str.is_empty() {
-                        orig.journal_date
-                    } else {
-                        match NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-                            Ok(d) => d,
-                            Err(_) => {
-                                println!("Invalid date format.");
-                                print_commands();
-                                continue;
+                match system.get_entry(id) {
+                    Ok(Some(orig)) => {
+                        let date_str = prompt(&format!("journal_date (YYYY-MM-DD) [{}]: ", orig.journal_date));
+                        let journal_date = if date_str.is_empty() {
+                            orig.journal_date
+                        } else {
+                            match NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                                Ok(d) => d,
+                                Err(_) => {
+                                    println!("Invalid date format.");
+                                    print_commands();
+                                    continue;
+                                }
                             }
+                        };
+                        let account_id: u32 = prompt(&format!("account_id [{}]: ", orig.account_id)).parse().unwrap_or(orig.account_id);
+                        let amount_debt: f64 = prompt(&format!("amount_debt [{}]: ", orig.amount_debt)).parse().unwrap_or(orig.amount_debt);
+                        let amount_credit: f64 = prompt(&format!("amount_credit [{}]: ", orig.amount_credit)).parse().unwrap_or(orig.amount_credit);
+                        let reconciled = prompt(&format!("reconciled (true/false) [{}]: ", orig.reconciled)).parse().unwrap_or(orig.reconciled);
+                        let updated = JournalEntry {
+                            id,
+                            journal_date,
+                            account_id,
+                            amount_debt,
+                            amount_credit,
+                            total: amount_debt - amount_credit, // for clarity, but update_entry also ensures this
+                            reconciled,
+                            isdeleted: "no".to_string(),
+                            transaction_id: orig.transaction_id,
+                        };
+                        match system.update_entry(id, updated) {
+                            Ok(()) => println!("Entry updated."),
+                            Err(e) => println!("Update failed: {}", e),
                         }
-                    };
-                    let account_id: u32 = prompt(&format!("account_id [{}]: ", orig.account_id)).parse().unwrap_or(orig.account_id);
-                    let amount_debt: f64 = prompt(&format!("amount_debt [{}]: ", orig.amount_debt)).parse().unwrap_or(orig.amount_debt);
-                    let amount_credit: f64 = prompt(&format!("amount_credit [{}]: ", orig.amount_credit)).parse().unwrap_or(orig.amount_credit);
-                    let reconciled = prompt(&format!("reconciled (true/false) [{}]: ", orig.reconciled)).parse().unwrap_or(orig.reconciled);
-                    let updated = JournalEntry {
-                        id,
-                        journal_date,
+                    }
+                    Ok(None) => println!("Entry not found."),
+                    Err(e) => println!("Get failed: {}", e),
+                }
+                print_commands();
+            }
+            "delete" => {
+                let id: u32 = prompt("id: ").parse().unwrap_or(0);
+                match system.delete_entry(id) {
+                    Ok(()) => println!("Entry deleted."),
+                    Err(AcctError::NotFound(id)) => println!("No entry with id {}.", id),
+                    Err(e) => println!("Delete failed: {}", e),
+                }
+                print_commands();
+            }
+            "post" => {
+                let date_str = prompt("date (YYYY-MM-DD): ");
+                let date = match NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
+                    Ok(d) => d,
+                    Err(_) => {
+                        println!("Invalid date format.");
+                        print_commands();
+                        continue;
+                    }
+                };
+                let description = prompt("description: ");
+                let mut lines = Vec::new();
+                loop {
+                    let account_id_str = prompt(&format!("line {} account_id (blank to finish): ", lines.len() + 1));
+                    if account_id_str.is_empty() {
+                        break;
+                    }
+                    let account_id: u32 = account_id_str.parse().unwrap_or(0);
+                    let amount_debt: f64 = prompt("amount_debt: ").parse().unwrap_or(0.0);
+                    let amount_credit: f64 = prompt("amount_credit: ").parse().unwrap_or(0.0);
+                    lines.push(JournalEntry {
+                        id: 0,
+                        journal_date: date,
                         account_id,
                         amount_debt,
                         amount_credit,
-                        total: amount_debt - amount_credit, // for clarity, but update_entry also ensures this
-                        reconciled,
+                        total: amount_debt - amount_credit,
+                        reconciled: false,
                         isdeleted: "no".to_string(),
-                    };
-                    if system.update_entry(id, updated) {
-                        println!("Entry updated.");
-                    } else {
-                        println!("Update failed.");
+                        transaction_id: None,
+                    });
+                }
+                let tx = Transaction { id: 0, date, description, lines };
+                match system.post_transaction(tx) {
+                    Ok(tx_id) => println!("Transaction {} posted.", tx_id),
+                    Err(AcctError::Imbalance { total_debt, total_credit }) => {
+                        println!("Transaction rejected: debits {} != credits {}", total_debt, total_credit)
                     }
+                    Err(e) => println!("Post failed: {}", e),
+                }
+                print_commands();
+            }
+            "trial" => {
+                let as_of_str = prompt("as_of (YYYY-MM-DD, blank for all time): ");
+                let as_of = if as_of_str.is_empty() {
+                    None
                 } else {
-                    println!("Entry not found.");
+                    match NaiveDate::parse_from_str(&as_of_str, "%Y-%m-%d") {
+                        Ok(d) => Some(d),
+                        Err(_) => {
+                            println!("Invalid date format.");
+                            print_commands();
+                            continue;
+                        }
+                    }
+                };
+                match system.trial_balance(as_of) {
+                    Ok(balances) => {
+                        let mut grand_debt = 0.0;
+                        let mut grand_credit = 0.0;
+                        println!("Trial Balance:");
+                        for b in &balances {
+                            println!(
+                                "account {}: debt {:.2}, credit {:.2}, total {:.2}",
+                                b.account_id, b.total_debt, b.total_credit, b.total
+                            );
+                            grand_debt += b.total_debt;
+                            grand_credit += b.total_credit;
+                        }
+                        println!("Grand totals: debt {:.2}, credit {:.2}", grand_debt, grand_credit);
+                    }
+                    Err(e) => println!("Trial balance failed: {}", e),
                 }
                 print_commands();
             }
-            "delete" => {
-                let id: u32 = prompt("id: ").parse().unwrap_or(0);
-                if system.delete_entry(id) {
-                    println!("Entry deleted.");
+            "ledger" => {
+                let account_id: u32 = prompt("account_id: ").parse().unwrap_or(0);
+                let from_str = prompt("from (YYYY-MM-DD): ");
+                let to_str = prompt("to (YYYY-MM-DD): ");
+                let from = match NaiveDate::parse_from_str(&from_str, "%Y-%m-%d") {
+                    Ok(d) => d,
+                    Err(_) => {
+                        println!("Invalid date format.");
+                        print_commands();
+                        continue;
+                    }
+                };
+                let to = match NaiveDate::parse_from_str(&to_str, "%Y-%m-%d") {
+                    Ok(d) => d,
+                    Err(_) => {
+                        println!("Invalid date format.");
+                        print_commands();
+                        continue;
+                    }
+                };
+                println!("Ledger for account {}:", account_id);
+                match system.account_ledger(account_id, from, to) {
+                    Ok(rows) => {
+                        for row in rows {
+                            println!(
+                                "{} | {:?} | running balance {:.2}",
+                                row.entry.journal_date, row.entry, row.running_balance
+                            );
+                        }
+                    }
+                    Err(e) => println!("Ledger failed: {}", e),
+                }
+                print_commands();
+            }
+            "export" => {
+                let path = prompt("path: ");
+                match system.export_csv(&path) {
+                    Ok(()) => println!("Exported to {}.", path),
+                    Err(e) => println!("Export failed: {}", e),
+                }
+                print_commands();
+            }
+            "import" => {
+                let path = prompt("path: ");
+                match system.import_csv(&path) {
+                    Ok(report) => {
+                        println!("Imported {} entries.", report.imported);
+                        for warning in &report.warnings {
+                            println!("Warning: {}", warning);
+                        }
+                    }
+                    Err(e) => println!("Import failed: {}", e),
+                }
+                print_commands();
+            }
+            "recur-add" => {
+                let cadence_str = prompt("cadence (daily/weekly/monthly/yearly): ").to_lowercase();
+                let cadence = match cadence_str.as_str() {
+                    "daily" => Cadence::Daily,
+                    "weekly" => Cadence::Weekly,
+                    "monthly" => Cadence::Monthly,
+                    "yearly" => Cadence::Yearly,
+                    _ => {
+                        println!("Unknown cadence.");
+                        print_commands();
+                        continue;
+                    }
+                };
+                let next_run_str = prompt("next_run (YYYY-MM-DD): ");
+                let next_run = match NaiveDate::parse_from_str(&next_run_str, "%Y-%m-%d") {
+                    Ok(d) => d,
+                    Err(_) => {
+                        println!("Invalid date format.");
+                        print_commands();
+                        continue;
+                    }
+                };
+                let end_str = prompt("end (YYYY-MM-DD, blank for none): ");
+                let end = if end_str.is_empty() {
+                    None
                 } else {
-                    println!("Delete failed.");
+                    match NaiveDate::parse_from_str(&end_str, "%Y-%m-%d") {
+                        Ok(d) => Some(d),
+                        Err(_) => {
+                            println!("Invalid date format.");
+                            print_commands();
+                            continue;
+                        }
+                    }
+                };
+                let account_id: u32 = prompt("account_id: ").parse().unwrap_or(0);
+                let amount_debt: f64 = prompt("amount_debt: ").parse().unwrap_or(0.0);
+                let amount_credit: f64 = prompt("amount_credit: ").parse().unwrap_or(0.0);
+                let reconciled = prompt("reconciled (true/false): ") == "true";
+                let template = JournalEntry {
+                    id: 0,
+                    journal_date: next_run,
+                    account_id,
+                    amount_debt,
+                    amount_credit,
+                    total: amount_debt - amount_credit,
+                    reconciled,
+                    isdeleted: "no".to_string(),
+                    transaction_id: None,
+                };
+                match system.recur_add(template, cadence, next_run, end) {
+                    Ok(id) => println!("Recurring entry {} added.", id),
+                    Err(e) => println!("Add failed: {}", e),
+                }
+                print_commands();
+            }
+            "recur-list" => {
+                println!("Recurring Entries:");
+                for rec in system.recur_list() {
+                    println!("{:?}", rec);
+                }
+                print_commands();
+            }
+            "run" => {
+                let today = chrono::Local::now().date_naive();
+                match system.run_due(today) {
+                    Ok(posted) => println!("Posted {} due entries.", posted),
+                    Err(e) => println!("Run failed: {}", e),
+                }
+                print_commands();
+            }
+            "compact" => {
+                match system.compact() {
+                    Ok(()) => println!("Log compacted."),
+                    Err(e) => println!("Compact failed: {}", e),
                 }
                 print_commands();
             }
@@ -227,4 +353,4 @@ fn main() {
             }
         }
     }
-}
\ No newline at end of file
+}